@@ -1,20 +1,32 @@
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use std::io::Cursor;
 use image::codecs::jpeg::JpegEncoder;
 
 use lopdf::{Document, Object, ObjectId};
 use image::GenericImageView;
 use thiserror::Error;
 
+use aes::{Aes128, Aes256};
+use cbc::cipher::{block_padding::NoPadding, block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::Write as _;
+
 #[derive(Error, Debug)]
 enum RustyPdfError {
-    #[error("IO error: {0}")] 
+    #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("PDF parse error: {0}")]
     PdfParse(#[from] lopdf::Error),
     #[error("Invalid scale value (expected 1..=10): {0}")]
     BadScale(i32),
+    #[error("incorrect password for encrypted PDF")]
+    InvalidPassword,
+    #[error("unsupported encryption scheme: {0}")]
+    UnsupportedEncryption(String),
 }
 
 impl From<RustyPdfError> for PyErr {
@@ -23,31 +35,562 @@ impl From<RustyPdfError> for PyErr {
     }
 }
 
-/// Merge multiple PDFs preserving page order.
-fn merge_impl(inputs: &[&str], output: &str) -> Result<(), RustyPdfError> {
-    let mut target_doc = Document::with_version("1.5");
-    let mut max_id = 1u32;
+// --- Standard security handler (PDF 32000-1:2008, 7.6) -------------------------------------
+
+/// Standard 32-byte padding string used to pad/truncate passwords (Algorithm 2, step a).
+const PASSWORD_PAD: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+fn pad_password(password: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let n = password.len().min(32);
+    out[..n].copy_from_slice(&password[..n]);
+    out[n..].copy_from_slice(&PASSWORD_PAD[..32 - n]);
+    out
+}
+
+/// RC4 key-scheduling and pseudo-random generation. Standard security handler key lengths are
+/// runtime-variable (5..=32 bytes), which rules out the `rc4` crate's compile-time-sized
+/// `Rc4<KeySize>`, so we hand-roll the algorithm here instead.
+fn rc4_crypt(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut s: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut j: u8 = 0;
+    for i in 0..256 {
+        j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+        s.swap(i, j as usize);
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    let (mut i, mut j) = (0u8, 0u8);
+    for &byte in data {
+        i = i.wrapping_add(1);
+        j = j.wrapping_add(s[i as usize]);
+        s.swap(i as usize, j as usize);
+        let k = s[(s[i as usize].wrapping_add(s[j as usize])) as usize];
+        out.push(byte ^ k);
+    }
+    out
+}
+
+fn aes_cbc_decrypt(key: &[u8], data: &[u8]) -> Result<Vec<u8>, RustyPdfError> {
+    if data.len() < 16 {
+        return Ok(Vec::new());
+    }
+    let (iv, ciphertext) = data.split_at(16);
+    let mut buf = ciphertext.to_vec();
+    let plain = if key.len() == 32 {
+        cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+            .map_err(|e| RustyPdfError::UnsupportedEncryption(e.to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .map_err(|_| RustyPdfError::UnsupportedEncryption("bad AES padding".into()))?
+            .to_vec()
+    } else {
+        cbc::Decryptor::<Aes128>::new_from_slices(key, iv)
+            .map_err(|e| RustyPdfError::UnsupportedEncryption(e.to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .map_err(|_| RustyPdfError::UnsupportedEncryption("bad AES padding".into()))?
+            .to_vec()
+    };
+    Ok(plain)
+}
+
+fn aes_cbc_encrypt(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut iv = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut iv);
+    let msg_len = data.len();
+    let mut buf = data.to_vec();
+    buf.resize(msg_len + 16, 0); // room for one block of PKCS7 padding
+    let ciphertext = if key.len() == 32 {
+        cbc::Encryptor::<Aes256>::new_from_slices(key, &iv)
+            .expect("AES-256 key/IV must be 32/16 bytes")
+            .encrypt_padded_mut::<Pkcs7>(&mut buf, msg_len)
+            .expect("buffer has room for PKCS7 padding")
+            .to_vec()
+    } else {
+        cbc::Encryptor::<Aes128>::new_from_slices(key, &iv)
+            .expect("AES-128 key/IV must be 16/16 bytes")
+            .encrypt_padded_mut::<Pkcs7>(&mut buf, msg_len)
+            .expect("buffer has room for PKCS7 padding")
+            .to_vec()
+    };
+    let mut out = iv.to_vec();
+    out.extend_from_slice(&ciphertext);
+    out
+}
+
+/// AES-128-CBC with no padding, for the block-aligned intermediate input that Algorithm 2.B
+/// encrypts on each round, rather than PDF string/stream bodies, which always use Pkcs7 via
+/// [`aes_cbc_encrypt`]/[`aes_cbc_decrypt`].
+fn aes_cbc_encrypt_no_padding(key: &[u8; 16], iv: &[u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut buf = data.to_vec();
+    let len = buf.len();
+    cbc::Encryptor::<Aes128>::new_from_slices(key, iv)
+        .expect("AES-128 key/IV must be 16/16 bytes")
+        .encrypt_padded_mut::<NoPadding>(&mut buf, len)
+        .expect("input is already block-aligned")
+        .to_vec()
+}
+
+fn aes256_cbc_decrypt_no_padding(key: &[u8; 32], iv: &[u8; 16], data: &[u8]) -> Result<Vec<u8>, RustyPdfError> {
+    let mut buf = data.to_vec();
+    let plain = cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+        .map_err(|e| RustyPdfError::UnsupportedEncryption(e.to_string()))?
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .map_err(|_| RustyPdfError::UnsupportedEncryption("malformed /UE or /OE".into()))?
+        .to_vec();
+    Ok(plain)
+}
+
+/// Algorithm 2.B: the "hardened" hash used by revision 6 to turn a password, salt and (for the
+/// owner hash) the `/U` string into a 32-byte key/validation hash. Revision 5 uses a single
+/// round of this (plain SHA-256 of password, salt and `udata`), which is the `revision < 6`
+/// early return below.
+fn hash_revision_6(password: &[u8], salt: &[u8], udata: &[u8], revision: i64) -> Vec<u8> {
+    let mut input = Vec::with_capacity(password.len() + salt.len() + udata.len());
+    input.extend_from_slice(password);
+    input.extend_from_slice(salt);
+    input.extend_from_slice(udata);
+    let mut k = Sha256::digest(&input).to_vec();
+
+    if revision < 6 {
+        return k;
+    }
+
+    let mut round = 0usize;
+    loop {
+        let mut k1 = Vec::with_capacity(64 * (password.len() + k.len() + udata.len()));
+        for _ in 0..64 {
+            k1.extend_from_slice(password);
+            k1.extend_from_slice(&k);
+            k1.extend_from_slice(udata);
+        }
+        let key: [u8; 16] = k[0..16].try_into().unwrap();
+        let iv: [u8; 16] = k[16..32].try_into().unwrap();
+        let e = aes_cbc_encrypt_no_padding(&key, &iv, &k1);
+
+        let modulus = e[0..16].iter().map(|&b| b as u32).sum::<u32>() % 3;
+        k = match modulus {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+
+        round += 1;
+        if round >= 64 && (*e.last().unwrap() as usize) <= round - 32 {
+            break;
+        }
+    }
+    k.truncate(32);
+    k
+}
+
+/// Algorithm 2: compute the file encryption key from the (padded) user password, `/O`, `/P`
+/// and the first element of the trailer `/ID`. `key_len` is clamped by the caller to `..=16`
+/// since it ultimately indexes into a 16-byte MD5 digest.
+fn compute_file_key(padded_password: &[u8; 32], o_entry: &[u8], p: i32, id0: &[u8], revision: i32, key_len: usize) -> Vec<u8> {
+    let mut input = Vec::with_capacity(32 + o_entry.len() + 4 + id0.len());
+    input.extend_from_slice(padded_password);
+    input.extend_from_slice(o_entry);
+    input.extend_from_slice(&p.to_le_bytes());
+    input.extend_from_slice(id0);
+
+    let mut digest = md5::compute(&input).0.to_vec();
+    if revision >= 3 {
+        for _ in 0..50 {
+            digest = md5::compute(&digest[..key_len]).0.to_vec();
+        }
+    }
+    digest.truncate(key_len);
+    digest
+}
+
+/// Algorithm 3: compute `/O` from the padded owner and user passwords.
+fn compute_owner_entry(padded_owner: &[u8; 32], padded_user: &[u8; 32], revision: i32, key_len: usize) -> Vec<u8> {
+    let mut digest = md5::compute(padded_owner).0.to_vec();
+    if revision >= 3 {
+        for _ in 0..50 {
+            digest = md5::compute(&digest[..key_len]).0.to_vec();
+        }
+    }
+    let rc4_key = &digest[..key_len];
+
+    let mut result = rc4_crypt(rc4_key, padded_user);
+    if revision >= 3 {
+        for i in 1u8..=19 {
+            let round_key: Vec<u8> = rc4_key.iter().map(|b| b ^ i).collect();
+            result = rc4_crypt(&round_key, &result);
+        }
+    }
+    result
+}
+
+/// Algorithms 4/5: compute `/U` from the file encryption key (and, for revision >= 3, the
+/// first trailer `/ID` element).
+fn compute_user_entry(file_key: &[u8], id0: &[u8], revision: i32) -> Vec<u8> {
+    if revision == 2 {
+        rc4_crypt(file_key, &PASSWORD_PAD)
+    } else {
+        let mut input = PASSWORD_PAD.to_vec();
+        input.extend_from_slice(id0);
+        let digest = md5::compute(&input).0;
+
+        let mut result = rc4_crypt(file_key, &digest);
+        for i in 1u8..=19 {
+            let round_key: Vec<u8> = file_key.iter().map(|b| b ^ i).collect();
+            result = rc4_crypt(&round_key, &result);
+        }
+        result.resize(32, 0);
+        result
+    }
+}
+
+/// Algorithm 1: derive the per-object key from the file key and the object/generation number.
+/// AES streams additionally mix in the fixed "sAlT" suffix. V5/V6 (AES-256) handlers use the
+/// file key directly for every object instead, so `is_aes256` skips the derivation entirely.
+fn object_key(file_key: &[u8], id: ObjectId, use_aes: bool, is_aes256: bool) -> Vec<u8> {
+    if is_aes256 {
+        return file_key.to_vec();
+    }
+    let mut input = file_key.to_vec();
+    input.push((id.0 & 0xff) as u8);
+    input.push(((id.0 >> 8) & 0xff) as u8);
+    input.push(((id.0 >> 16) & 0xff) as u8);
+    input.push((id.1 & 0xff) as u8);
+    input.push(((id.1 >> 8) & 0xff) as u8);
+    if use_aes {
+        input.extend_from_slice(b"sAlT");
+    }
+    let digest = md5::compute(&input).0;
+    let n = (file_key.len() + 5).min(16);
+    digest[..n].to_vec()
+}
+
+/// Recursively decrypt every string and stream body reachable from `obj` with the given
+/// per-object key, leaving the object tree shape untouched.
+fn decrypt_object(obj: &mut Object, key: &[u8], use_aes: bool) {
+    match obj {
+        Object::String(s, _) => {
+            *s = if use_aes { aes_cbc_decrypt(key, s).unwrap_or_else(|_| s.clone()) } else { rc4_crypt(key, s) };
+        }
+        Object::Array(items) => {
+            for item in items.iter_mut() {
+                decrypt_object(item, key, use_aes);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (_, v) in dict.iter_mut() {
+                decrypt_object(v, key, use_aes);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, v) in stream.dict.iter_mut() {
+                decrypt_object(v, key, use_aes);
+            }
+            stream.content = if use_aes {
+                aes_cbc_decrypt(key, &stream.content).unwrap_or_else(|_| stream.content.clone())
+            } else {
+                rc4_crypt(key, &stream.content)
+            };
+        }
+        _ => {}
+    }
+}
+
+fn encrypt_object(obj: &mut Object, key: &[u8], use_aes: bool) {
+    match obj {
+        Object::String(s, _) => {
+            *s = if use_aes { aes_cbc_encrypt(key, s) } else { rc4_crypt(key, s) };
+        }
+        Object::Array(items) => {
+            for item in items.iter_mut() {
+                encrypt_object(item, key, use_aes);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (_, v) in dict.iter_mut() {
+                encrypt_object(v, key, use_aes);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, v) in stream.dict.iter_mut() {
+                encrypt_object(v, key, use_aes);
+            }
+            stream.content = if use_aes { aes_cbc_encrypt(key, &stream.content) } else { rc4_crypt(key, &stream.content) };
+        }
+        _ => {}
+    }
+}
+
+/// For `V=4` crypt filters: look up the `/StmF`-named filter in `/CF` and report whether its
+/// `/CFM` is one of the AES methods (`AESV2`/`AESV3`) rather than RC4 (`V2`) or no encryption
+/// at all (`Identity`). `V=4` only fixes the *dictionary layout*, not the cipher — a real V=4
+/// file can legally use RC4 via `CFM=V2`, so the cipher can't be inferred from `/V` alone.
+fn crypt_filter_is_aes(encrypt_dict: &lopdf::Dictionary) -> bool {
+    let stmf = match encrypt_dict.get(b"StmF") { Ok(Object::Name(n)) => n.clone(), _ => return false };
+    if stmf == b"Identity" {
+        return false;
+    }
+    let cfm = match encrypt_dict.get(b"CF") {
+        Ok(Object::Dictionary(cf)) => match cf.get(&stmf) {
+            Ok(Object::Dictionary(filter)) => match filter.get(b"CFM") {
+                Ok(Object::Name(m)) => m.clone(),
+                _ => return false,
+            },
+            _ => return false,
+        },
+        _ => return false,
+    };
+    cfm == b"AESV2" || cfm == b"AESV3"
+}
+
+/// If `doc`'s trailer has an `/Encrypt` entry, validate `password` against `/U` and decrypt
+/// every string and stream in place, then drop `/Encrypt` so the in-memory document behaves
+/// like a plain one. No-op when the document isn't encrypted.
+fn decrypt_document(doc: &mut Document, password: &str) -> Result<(), RustyPdfError> {
+    let encrypt_ref = match doc.trailer.get(b"Encrypt") {
+        Ok(&Object::Reference(id)) => id,
+        _ => return Ok(()),
+    };
+
+    let id0 = match doc.trailer.get(b"ID") {
+        Ok(Object::Array(arr)) => match arr.first() {
+            Some(Object::String(bytes, _)) => bytes.clone(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    let encrypt_dict = doc.get_object(encrypt_ref)?.as_dict()?.clone();
+    let v = match encrypt_dict.get(b"V") { Ok(Object::Integer(n)) => *n, _ => 0 };
+    let r = match encrypt_dict.get(b"R") { Ok(Object::Integer(n)) => *n, _ => 2 };
+    let o_entry = match encrypt_dict.get(b"O") { Ok(Object::String(s, _)) => s.clone(), _ => Vec::new() };
+    let u_entry = match encrypt_dict.get(b"U") { Ok(Object::String(s, _)) => s.clone(), _ => Vec::new() };
+    let p = match encrypt_dict.get(b"P") { Ok(Object::Integer(n)) => *n as i32, _ => 0 };
+    let length_bits = match encrypt_dict.get(b"Length") { Ok(Object::Integer(n)) => *n as usize, _ => 40 };
+
+    let is_aes256 = v == 5;
+    let use_aes = if is_aes256 { true } else if v == 4 { crypt_filter_is_aes(&encrypt_dict) } else { false };
+
+    let file_key = if is_aes256 {
+        // Algorithm 2.A: V5/R5-R6 (AES-256) derives the file key from a SHA-256-family hash of
+        // the password and a salt carried in `/U`, then unwraps `/UE` with it — nothing here
+        // goes through MD5/RC4. The password is validated against `/U` before `/UE` is unwrapped.
+        let ue_entry = match encrypt_dict.get(b"UE") { Ok(Object::String(s, _)) => s.clone(), _ => Vec::new() };
+        if u_entry.len() < 48 || ue_entry.len() != 32 {
+            return Err(RustyPdfError::UnsupportedEncryption("malformed V5 /U or /UE entry".into()));
+        }
+        let password_bytes = password.as_bytes();
+        let validation_salt = &u_entry[32..40];
+        let key_salt = &u_entry[40..48];
+
+        let validation_hash = hash_revision_6(password_bytes, validation_salt, &[], r);
+        if validation_hash != u_entry[0..32] {
+            return Err(RustyPdfError::InvalidPassword);
+        }
+
+        let intermediate_key: [u8; 32] = hash_revision_6(password_bytes, key_salt, &[], r)
+            .try_into()
+            .expect("hash_revision_6 always returns 32 bytes");
+        aes256_cbc_decrypt_no_padding(&intermediate_key, &[0u8; 16], &ue_entry)?
+    } else {
+        let key_len: usize = (length_bits / 8).clamp(5, 16);
+        let padded = pad_password(password.as_bytes());
+        let key = compute_file_key(&padded, &o_entry, p, &id0, r as i32, key_len);
+
+        if r <= 4 {
+            let expected_u = compute_user_entry(&key, &id0, r as i32);
+            let check_len = if r == 2 { 32 } else { 16 };
+            if expected_u[..check_len] != u_entry[..check_len.min(u_entry.len())] {
+                return Err(RustyPdfError::InvalidPassword);
+            }
+        }
+        key
+    };
+
+    let ids: Vec<ObjectId> = doc.objects.keys().filter(|id| **id != encrypt_ref).cloned().collect();
+    for id in ids {
+        let key = object_key(&file_key, id, use_aes, is_aes256);
+        if let Some(obj) = doc.objects.get_mut(&id) {
+            decrypt_object(obj, &key, use_aes);
+        }
+    }
+
+    doc.trailer.remove(b"Encrypt");
+    Ok(())
+}
+
+/// Protect `doc` with the standard security handler (RC4, 128-bit key, revision 3), setting
+/// `/Encrypt` and `/ID` on the trailer and encrypting every string and stream in place.
+fn encrypt_document(doc: &mut Document, user_password: &str, owner_password: &str, permissions: i32) -> Result<(), RustyPdfError> {
+    const KEY_LEN: usize = 16;
+    const REVISION: i32 = 3;
+    const VERSION: i32 = 2;
+
+    let id0 = match doc.trailer.get(b"ID") {
+        Ok(Object::Array(arr)) => match arr.first() {
+            Some(Object::String(bytes, _)) => bytes.clone(),
+            _ => {
+                let mut fresh = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut fresh);
+                fresh.to_vec()
+            }
+        },
+        _ => {
+            let mut fresh = [0u8; 16];
+            rand::thread_rng().fill_bytes(&mut fresh);
+            let id_obj = Object::String(fresh.to_vec(), lopdf::StringFormat::Literal);
+            doc.trailer.set("ID", Object::Array(vec![id_obj.clone(), id_obj]));
+            fresh.to_vec()
+        }
+    };
+
+    let padded_user = pad_password(user_password.as_bytes());
+    let owner_source = if owner_password.is_empty() { user_password.as_bytes() } else { owner_password.as_bytes() };
+    let padded_owner = pad_password(owner_source);
+
+    let o_entry = compute_owner_entry(&padded_owner, &padded_user, REVISION, KEY_LEN);
+    let file_key = compute_file_key(&padded_user, &o_entry, permissions, &id0, REVISION, KEY_LEN);
+    let u_entry = compute_user_entry(&file_key, &id0, REVISION);
+
+    let encrypt_dict = lopdf::dictionary! {
+        "Filter" => "Standard",
+        "V" => VERSION as i64,
+        "R" => REVISION as i64,
+        "O" => Object::String(o_entry, lopdf::StringFormat::Literal),
+        "U" => Object::String(u_entry, lopdf::StringFormat::Literal),
+        "P" => permissions as i64,
+        "Length" => (KEY_LEN * 8) as i64
+    };
+    let encrypt_ref = doc.add_object(encrypt_dict);
+    doc.trailer.set("Encrypt", encrypt_ref);
+
+    let ids: Vec<ObjectId> = doc.objects.keys().filter(|id| **id != encrypt_ref).cloned().collect();
+    for id in ids {
+        let key = object_key(&file_key, id, false, false);
+        if let Some(obj) = doc.objects.get_mut(&id) {
+            encrypt_object(obj, &key, false);
+        }
+    }
+
+    Ok(())
+}
 
-    // Helper to resolve inheritable key up the Pages tree
-    fn resolve_inherited(doc: &Document, id: ObjectId, key: &[u8]) -> Option<Object> {
-        let mut current_id = Some(id);
-        while let Some(cid) = current_id {
-            if let Ok(obj) = doc.get_object(cid) {
-                if let Ok(d) = obj.as_dict() {
-                    if let Ok(v) = d.get(key) { return Some(v.clone()); }
-                    if let Ok(&Object::Reference(parent_id)) = d.get(b"Parent") { current_id = Some(parent_id); } else { current_id = None; }
-                } else {
-                    break;
+/// Walk a name tree node (`/Names` leaf array and/or `/Kids` subtree), collecting
+/// every (key, value) pair reachable from it.
+fn collect_name_tree(doc: &Document, node_id: ObjectId, out: &mut Vec<(Vec<u8>, Object)>) {
+    if let Ok(obj) = doc.get_object(node_id) {
+        if let Ok(dict) = obj.as_dict() {
+            if let Ok(Object::Array(names)) = dict.get(b"Names") {
+                for pair in names.chunks(2) {
+                    if pair.len() == 2 {
+                        if let Object::String(key, _) = &pair[0] {
+                            out.push((key.clone(), pair[1].clone()));
+                        }
+                    }
+                }
+            }
+            if let Ok(Object::Array(kids)) = dict.get(b"Kids") {
+                for kid in kids {
+                    if let Object::Reference(kid_id) = kid {
+                        collect_name_tree(doc, *kid_id, out);
+                    }
                 }
+            }
+        }
+    }
+}
+
+/// Follow the `/First` -> `/Next` chain of an outline (or outline item) dictionary,
+/// returning the ObjectIds of its direct children in order. A visited set guards against a
+/// malformed or adversarially-crafted `/Next` cycle, the same way `mark_reachable` does.
+fn collect_outline_siblings(doc: &Document, parent_id: ObjectId) -> Vec<ObjectId> {
+    let mut siblings = Vec::new();
+    let mut visited: std::collections::BTreeSet<ObjectId> = std::collections::BTreeSet::new();
+    let first = doc
+        .get_object(parent_id)
+        .ok()
+        .and_then(|o| o.as_dict().ok())
+        .and_then(|d| d.get(b"First").ok())
+        .and_then(|v| if let Object::Reference(id) = v { Some(*id) } else { None });
+
+    let mut current = first;
+    while let Some(id) = current {
+        if !visited.insert(id) { break; }
+        siblings.push(id);
+        current = doc
+            .get_object(id)
+            .ok()
+            .and_then(|o| o.as_dict().ok())
+            .and_then(|d| d.get(b"Next").ok())
+            .and_then(|v| if let Object::Reference(next_id) = v { Some(*next_id) } else { None });
+    }
+    siblings
+}
+
+/// Resolve an inheritable Page attribute (`/Resources`, `/MediaBox`, `/CropBox`, `/Rotate`, ...)
+/// by walking up `/Parent` until it's found. A visited set guards against a malformed or
+/// adversarially-crafted `/Parent` cycle, the same way `mark_reachable` does.
+fn resolve_inherited(doc: &Document, id: ObjectId, key: &[u8]) -> Option<Object> {
+    let mut visited: std::collections::BTreeSet<ObjectId> = std::collections::BTreeSet::new();
+    let mut current_id = Some(id);
+    while let Some(cid) = current_id {
+        if !visited.insert(cid) { break; }
+        if let Ok(obj) = doc.get_object(cid) {
+            if let Ok(d) = obj.as_dict() {
+                if let Ok(v) = d.get(key) { return Some(v.clone()); }
+                if let Ok(&Object::Reference(parent_id)) = d.get(b"Parent") { current_id = Some(parent_id); } else { current_id = None; }
             } else {
                 break;
             }
+        } else {
+            break;
         }
-        None
     }
+    None
+}
 
-    for path in inputs {
+/// Resolve an AcroForm `/DR`'s subdictionary entries (`/Font`, `/ColorSpace`, ...) that are given
+/// as indirect references — common in Adobe-generated forms — to their inline `Dictionary` value,
+/// so a later union over categories doesn't silently drop them.
+fn resolve_dr_subdicts(doc: &Document, dr: &lopdf::Dictionary) -> lopdf::Dictionary {
+    let mut resolved = lopdf::Dictionary::new();
+    for (category, value) in dr.iter() {
+        let sub = match value {
+            Object::Dictionary(sub) => Some(sub.clone()),
+            Object::Reference(r) => match doc.get_object(*r) {
+                Ok(Object::Dictionary(sub)) => Some(sub.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(sub) = sub {
+            resolved.set(category.clone(), Object::Dictionary(sub));
+        }
+    }
+    resolved
+}
+
+/// Merge multiple PDFs preserving page order, bookmarks (`/Outlines`) and named destinations.
+/// `password` is used to open any input that is encrypted with the standard security handler.
+fn merge_impl(inputs: &[&str], output: &str, password: Option<&str>) -> Result<(), RustyPdfError> {
+    let mut target_doc = Document::with_version("1.5");
+    let mut max_id = 1u32;
+
+    // Per-input outline roots (in input order) and merged named-destination entries.
+    let mut outline_roots: Vec<ObjectId> = Vec::new();
+    let mut dest_entries: Vec<(Vec<u8>, Object)> = Vec::new();
+
+    // Per-input AcroForm state: top-level field refs, /DR resource dict, /DA and /NeedAppearances.
+    let mut acroform_fields_by_doc: Vec<(usize, Vec<ObjectId>)> = Vec::new();
+    let mut dr_dicts: Vec<(usize, lopdf::Dictionary)> = Vec::new();
+    let mut shared_da: Option<Object> = None;
+    let mut any_need_appearances = false;
+
+    for (doc_index, path) in inputs.iter().enumerate() {
         let mut doc = Document::load(path)?;
+        decrypt_document(&mut doc, password.unwrap_or(""))?;
         // Renumber to avoid collisions in the target document
         doc.renumber_objects_with(max_id);
         max_id = doc.max_id + 1;
@@ -76,6 +619,78 @@ fn merge_impl(inputs: &[&str], output: &str) -> Result<(), RustyPdfError> {
             }
         }
 
+        // Capture outline tree and named destinations before the objects are moved.
+        // The renumbering above has already rewritten every internal Reference (including
+        // the explicit page refs inside /Dest arrays), so these ids are already final.
+        if let Ok(&Object::Reference(catalog_id)) = doc.trailer.get(b"Root") {
+            if let Ok(catalog_obj) = doc.get_object(catalog_id) {
+                if let Ok(catalog_dict) = catalog_obj.as_dict() {
+                    if let Ok(&Object::Reference(outlines_id)) = catalog_dict.get(b"Outlines") {
+                        outline_roots.push(outlines_id);
+                    }
+
+                    // Legacy /Dests dictionary: name -> destination.
+                    if let Ok(&Object::Reference(dests_id)) = catalog_dict.get(b"Dests") {
+                        if let Ok(Object::Dictionary(dests_dict)) = doc.get_object(dests_id) {
+                            for (k, v) in dests_dict.iter() {
+                                let tagged = [format!("d{}_", doc_index).as_bytes(), k].concat();
+                                dest_entries.push((tagged, v.clone()));
+                            }
+                        }
+                    }
+
+                    // Modern /Names /Dests name tree.
+                    if let Ok(&Object::Reference(names_id)) = catalog_dict.get(b"Names") {
+                        if let Ok(names_obj) = doc.get_object(names_id) {
+                            if let Ok(names_dict) = names_obj.as_dict() {
+                                if let Ok(&Object::Reference(tree_id)) = names_dict.get(b"Dests") {
+                                    let mut collected = Vec::new();
+                                    collect_name_tree(&doc, tree_id, &mut collected);
+                                    for (k, v) in collected {
+                                        let tagged = [format!("d{}_", doc_index).as_bytes(), &k[..]].concat();
+                                        dest_entries.push((tagged, v));
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // /AcroForm: fields, default resources/appearance, NeedAppearances.
+                    if let Ok(&Object::Reference(acroform_id)) = catalog_dict.get(b"AcroForm") {
+                        if let Ok(acroform_dict) = doc.get_object(acroform_id).and_then(|o| o.as_dict()) {
+                            if let Ok(Object::Array(fields)) = acroform_dict.get(b"Fields") {
+                                let field_ids: Vec<ObjectId> = fields
+                                    .iter()
+                                    .filter_map(|o| if let Object::Reference(r) = o { Some(*r) } else { None })
+                                    .collect();
+                                if !field_ids.is_empty() {
+                                    acroform_fields_by_doc.push((doc_index, field_ids));
+                                }
+                            }
+                            let dr = match acroform_dict.get(b"DR") {
+                                Ok(Object::Dictionary(dr)) => Some(dr.clone()),
+                                Ok(&Object::Reference(dr_id)) => match doc.get_object(dr_id) {
+                                    Ok(Object::Dictionary(dr)) => Some(dr.clone()),
+                                    _ => None,
+                                },
+                                _ => None,
+                            };
+                            if let Some(dr) = dr { dr_dicts.push((doc_index, resolve_dr_subdicts(&doc, &dr))); }
+                            if shared_da.is_none() {
+                                if let Ok(da) = acroform_dict.get(b"DA") { shared_da = Some(da.clone()); }
+                            }
+                            if let Ok(Object::Boolean(true)) = acroform_dict.get(b"NeedAppearances") {
+                                any_need_appearances = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Keep the id counter past every moved object, so the objects added below get fresh ids.
+        target_doc.max_id = doc.max_id;
+
         // Move all objects into target
         let moved: Vec<(ObjectId, Object)> = doc.objects.iter().map(|(id, o)| (*id, o.clone())).collect();
         target_doc.objects.extend(moved.into_iter());
@@ -103,8 +718,129 @@ fn merge_impl(inputs: &[&str], output: &str) -> Result<(), RustyPdfError> {
         }
     }
 
+    // Splice every input's top-level outline items under one merged root, fixing up
+    // the Parent/Next/Prev/First/Last/Count links so the combined tree is well-formed.
+    let mut top_items: Vec<ObjectId> = Vec::new();
+    let mut total_count: i64 = 0;
+    for &root_id in &outline_roots {
+        let siblings = collect_outline_siblings(&target_doc, root_id);
+        if let Ok(obj) = target_doc.get_object(root_id) {
+            if let Ok(dict) = obj.as_dict() {
+                total_count += match dict.get(b"Count") {
+                    Ok(Object::Integer(c)) => c.unsigned_abs() as i64,
+                    _ => siblings.len() as i64,
+                };
+            }
+        }
+        top_items.extend(siblings);
+    }
+
+    let outlines_id = if !top_items.is_empty() {
+        let id = target_doc.add_object(lopdf::dictionary! {
+            "Type" => "Outlines",
+            "First" => Object::Reference(top_items[0]),
+            "Last" => Object::Reference(*top_items.last().unwrap()),
+            "Count" => total_count
+        });
+
+        for (i, &item_id) in top_items.iter().enumerate() {
+            if let Ok(obj) = target_doc.get_object_mut(item_id) {
+                if let Ok(dict) = obj.as_dict_mut() {
+                    dict.set("Parent", id);
+                    if i > 0 { dict.set("Prev", top_items[i - 1]); } else { dict.remove(b"Prev"); }
+                    if i + 1 < top_items.len() { dict.set("Next", top_items[i + 1]); } else { dict.remove(b"Next"); }
+                }
+            }
+        }
+        Some(id)
+    } else {
+        None
+    };
+
+    // Merge named destinations into a single, sorted name tree.
+    let names_dict_id = if !dest_entries.is_empty() {
+        dest_entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut flat_names: Vec<Object> = Vec::with_capacity(dest_entries.len() * 2);
+        for (key, val) in dest_entries {
+            flat_names.push(Object::String(key, lopdf::StringFormat::Literal));
+            flat_names.push(val);
+        }
+        let names_tree_id = target_doc.add_object(lopdf::dictionary! { "Names" => Object::Array(flat_names) });
+        Some(target_doc.add_object(lopdf::dictionary! { "Dests" => names_tree_id }))
+    } else {
+        None
+    };
+
+    // Merge every input's AcroForm into one: concatenate /Fields (disambiguating colliding
+    // partial names), union /DR resource subdictionaries, and OR /NeedAppearances.
+    let acroform_id = if !acroform_fields_by_doc.is_empty() {
+        let mut seen_names: std::collections::HashSet<Vec<u8>> = std::collections::HashSet::new();
+        let mut collides = false;
+        for (_, field_ids) in &acroform_fields_by_doc {
+            for &fid in field_ids {
+                if let Ok(Object::String(name, _)) = target_doc.get_object(fid).and_then(|o| o.as_dict()).and_then(|d| d.get(b"T")) {
+                    if !seen_names.insert(name.clone()) { collides = true; }
+                }
+            }
+        }
+
+        let mut merged_fields: Vec<Object> = Vec::new();
+        for (doc_index, field_ids) in &acroform_fields_by_doc {
+            if collides {
+                // Wrap this input's top-level fields under one parent node tagged by input index
+                // so fully-qualified field names no longer collide.
+                let parent_id = target_doc.add_object(lopdf::dictionary! {
+                    "T" => Object::String(format!("doc{}", doc_index).into_bytes(), lopdf::StringFormat::Literal),
+                    "Kids" => Object::Array(field_ids.iter().map(|id| Object::Reference(*id)).collect::<Vec<_>>())
+                });
+                for &fid in field_ids {
+                    if let Ok(field_obj) = target_doc.get_object_mut(fid) {
+                        if let Ok(dict) = field_obj.as_dict_mut() { dict.set("Parent", parent_id); }
+                    }
+                }
+                merged_fields.push(Object::Reference(parent_id));
+            } else {
+                merged_fields.extend(field_ids.iter().map(|id| Object::Reference(*id)));
+            }
+        }
+
+        // Union /DR subdictionaries (Font, ColorSpace, ...), renaming colliding resource keys.
+        let mut merged_categories: std::collections::HashMap<Vec<u8>, lopdf::Dictionary> = std::collections::HashMap::new();
+        for (doc_index, dr) in &dr_dicts {
+            for (category, value) in dr.iter() {
+                if let Object::Dictionary(sub) = value {
+                    let entry = merged_categories.entry(category.clone()).or_default();
+                    for (res_key, res_val) in sub.iter() {
+                        if entry.has(res_key) {
+                            let renamed = [res_key.as_slice(), format!("_d{}", doc_index).as_bytes()].concat();
+                            entry.set(renamed, res_val.clone());
+                        } else {
+                            entry.set(res_key.clone(), res_val.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut acroform_dict = lopdf::dictionary! { "Fields" => Object::Array(merged_fields) };
+        if !merged_categories.is_empty() {
+            let mut merged_dr = lopdf::Dictionary::new();
+            for (category, sub) in merged_categories { merged_dr.set(category, Object::Dictionary(sub)); }
+            acroform_dict.set("DR", Object::Dictionary(merged_dr));
+        }
+        if let Some(da) = &shared_da { acroform_dict.set("DA", da.clone()); }
+        if any_need_appearances { acroform_dict.set("NeedAppearances", true); }
+        Some(target_doc.add_object(acroform_dict))
+    } else {
+        None
+    };
+
     // Catalog
-    let catalog_id = target_doc.add_object(lopdf::dictionary!{ "Type" => "Catalog", "Pages" => pages_id });
+    let mut catalog_dict = lopdf::dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+    if let Some(id) = outlines_id { catalog_dict.set("Outlines", id); }
+    if let Some(id) = names_dict_id { catalog_dict.set("Names", id); }
+    if let Some(id) = acroform_id { catalog_dict.set("AcroForm", id); }
+    let catalog_id = target_doc.add_object(catalog_dict);
     target_doc.trailer.set("Root", catalog_id);
 
     target_doc.compress();
@@ -112,15 +848,329 @@ fn merge_impl(inputs: &[&str], output: &str) -> Result<(), RustyPdfError> {
     Ok(())
 }
 
-/// Very naive compression: downscale JPEG/PNG images by a factor derived from scale (1..10) and re-embed.
-/// This is a placeholder; full fidelity PDF image handling is complex.
-fn compress_impl(input: &str, output: &str, scale: i32) -> Result<(), RustyPdfError> {
+/// Mark `obj_id` and, recursively, everything reachable from it (`/Resources`, `/Contents`,
+/// fonts, XObjects, annotations, ...) as live. `/Parent` is deliberately not followed: for a
+/// Page that would walk straight back up into the original Pages tree and pull in every page.
+fn mark_reachable(doc: &Document, obj_id: ObjectId, visited: &mut std::collections::BTreeSet<ObjectId>) {
+    if !visited.insert(obj_id) { return; }
+    if let Ok(obj) = doc.get_object(obj_id) {
+        mark_reachable_in(doc, obj, visited);
+    }
+}
+
+fn mark_reachable_in(doc: &Document, obj: &Object, visited: &mut std::collections::BTreeSet<ObjectId>) {
+    match obj {
+        Object::Reference(id) => mark_reachable(doc, *id, visited),
+        Object::Array(items) => {
+            for item in items {
+                mark_reachable_in(doc, item, visited);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (key, v) in dict.iter() {
+                if key == b"Parent" { continue; }
+                mark_reachable_in(doc, v, visited);
+            }
+        }
+        Object::Stream(stream) => {
+            for (key, v) in stream.dict.iter() {
+                if key == b"Parent" { continue; }
+                mark_reachable_in(doc, v, visited);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Write a new document containing only `pages` (1-based page numbers, in the order given),
+/// garbage-collecting every object not transitively reachable from them.
+fn split_impl(input: &str, output: &str, pages: &[u32]) -> Result<(), RustyPdfError> {
+    let mut doc = Document::load(input)?;
+
+    let page_map = doc.get_pages();
+    let retained_pages: Vec<ObjectId> = pages.iter().filter_map(|n| page_map.get(n).copied()).collect();
+    let retained_set: std::collections::BTreeSet<ObjectId> = retained_pages.iter().copied().collect();
+
+    // Make each retained page self-contained before the original Pages tree is swept away.
+    for &page_id in &retained_pages {
+        let mut to_set: Vec<(&[u8], Object)> = Vec::new();
+        if let Ok(obj) = doc.get_object(page_id) {
+            if let Ok(dict) = obj.as_dict() {
+                for &key in [&b"Resources"[..], &b"MediaBox"[..], &b"CropBox"[..], &b"Rotate"[..]].iter() {
+                    if !dict.has(key) {
+                        if let Some(val) = resolve_inherited(&doc, page_id, key) { to_set.push((key, val)); }
+                    }
+                }
+            }
+        }
+        if !to_set.is_empty() {
+            if let Ok(page_obj) = doc.get_object_mut(page_id) {
+                if let Ok(dict) = page_obj.as_dict_mut() {
+                    for (key, val) in to_set { dict.set(key, val); }
+                }
+            }
+        }
+    }
+
+    // Drop /Annots entries (e.g. link annotations) whose explicit destination page isn't retained,
+    // so the output has no dangling references.
+    for &page_id in &retained_pages {
+        let annot_refs: Vec<ObjectId> = match doc.get_object(page_id).ok().and_then(|o| o.as_dict().ok()).and_then(|d| d.get(b"Annots").ok()) {
+            Some(Object::Array(arr)) => arr.iter().filter_map(|o| if let Object::Reference(r) = o { Some(*r) } else { None }).collect(),
+            _ => continue,
+        };
+
+        // An explicit destination array's first element is the target page, either directly
+        // on the annotation's own /Dest or, more commonly, on its /A GoTo action's /D.
+        let dest_targets_dropped_page = |dest: &Object| -> bool {
+            match dest {
+                Object::Array(arr) => match arr.first() {
+                    Some(Object::Reference(target)) => !retained_set.contains(target),
+                    _ => false,
+                },
+                _ => false, // named/string destination: kept as-is, its target page is not checked
+            }
+        };
+
+        let kept: Vec<Object> = annot_refs
+            .into_iter()
+            .filter(|annot_id| match doc.get_object(*annot_id).ok().and_then(|o| o.as_dict().ok()) {
+                Some(annot_dict) => {
+                    let dest_dropped = annot_dict.get(b"Dest").ok().is_some_and(dest_targets_dropped_page);
+                    let action_dest_dropped = annot_dict
+                        .get(b"A")
+                        .ok()
+                        .and_then(|a| match a {
+                            Object::Reference(r) => doc.get_object(*r).ok(),
+                            other => Some(other),
+                        })
+                        .and_then(|a| a.as_dict().ok())
+                        .and_then(|a| a.get(b"D").ok())
+                        .is_some_and(dest_targets_dropped_page);
+                    !dest_dropped && !action_dest_dropped
+                }
+                None => false,
+            })
+            .map(Object::Reference)
+            .collect();
+
+        if let Ok(page_obj) = doc.get_object_mut(page_id) {
+            if let Ok(dict) = page_obj.as_dict_mut() {
+                if kept.is_empty() { dict.remove(b"Annots"); } else { dict.set("Annots", Object::Array(kept)); }
+            }
+        }
+    }
+
+    // Retain-and-sweep: mark everything reachable from a kept page, then drop the rest.
+    let mut visited: std::collections::BTreeSet<ObjectId> = std::collections::BTreeSet::new();
+    for &page_id in &retained_pages {
+        mark_reachable(&doc, page_id, &mut visited);
+    }
+    doc.objects.retain(|id, _| visited.contains(id));
+
+    // Rebuild a Pages tree with just the retained pages, in the order requested.
+    let page_refs: Vec<Object> = retained_pages.iter().map(|id| Object::Reference(*id)).collect();
+    let pages_id = doc.add_object(lopdf::dictionary! { "Type" => "Pages", "Kids" => Object::Array(page_refs.clone()), "Count" => page_refs.len() as i64 });
+    for &page_id in &retained_pages {
+        if let Ok(page_obj) = doc.get_object_mut(page_id) {
+            if let Ok(dict) = page_obj.as_dict_mut() {
+                dict.set("Parent", pages_id);
+            }
+        }
+    }
+
+    let catalog_id = doc.add_object(lopdf::dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+
+    doc.compress();
+    doc.save(output)?;
+    Ok(())
+}
+
+/// Read the image filter name actually used to store pixel data: the last entry of a filter
+/// chain (e.g. `[ASCII85Decode DCTDecode]`), or the bare `/Filter` name.
+fn image_filter_name(dict: &lopdf::Dictionary) -> Vec<u8> {
+    match dict.get(b"Filter") {
+        Ok(Object::Name(n)) => n.clone(),
+        Ok(Object::Array(arr)) => match arr.last() {
+            Some(Object::Name(n)) => n.clone(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Resolve a `/ColorSpace` entry (a name, an indirect reference to one, or an `Indexed` or
+/// `ICCBased` array) down to its device color space name and component count. For `Indexed`
+/// the name is `Indexed`, the count is that of the base space, and the third value is the raw
+/// lookup table (one entry of that many components per index, not converted to RGB).
+fn resolve_color_space(doc: &Document, cs: &Object) -> Option<(Vec<u8>, usize, Option<Vec<u8>>)> {
+    match cs {
+        Object::Name(n) => {
+            let comps = match n.as_slice() {
+                b"DeviceGray" | b"CalGray" => 1,
+                b"DeviceCMYK" => 4,
+                _ => 3,
+            };
+            Some((n.clone(), comps, None))
+        }
+        Object::Reference(r) => resolve_color_space(doc, doc.get_object(*r).ok()?),
+        Object::Array(arr) => {
+            if let Some(Object::Name(kind)) = arr.first() {
+                if kind == b"Indexed" && arr.len() >= 4 {
+                    let (_, base_comps, _) = resolve_color_space(doc, arr.get(1)?)?;
+                    let lookup_obj = match &arr[3] {
+                        Object::Reference(r) => doc.get_object(*r).ok()?,
+                        other => other,
+                    };
+                    let lookup = string_or_stream_bytes(lookup_obj)?;
+                    return Some((b"Indexed".to_vec(), base_comps, Some(lookup)));
+                }
+                if kind == b"ICCBased" && arr.len() >= 2 {
+                    // [/ICCBased streamRef]: the profile stream's /N gives the component count;
+                    // there's no device color space name in the ICC profile itself, so fall back
+                    // to the Device* space with matching component count.
+                    let stream_obj = match arr.get(1)? {
+                        Object::Reference(r) => doc.get_object(*r).ok()?,
+                        other => other,
+                    };
+                    let Object::Stream(stream) = stream_obj else { return None };
+                    let comps = match stream.dict.get(b"N") { Ok(Object::Integer(n)) => *n as usize, _ => return None };
+                    let base: &[u8] = match comps {
+                        1 => b"DeviceGray",
+                        4 => b"DeviceCMYK",
+                        _ => b"DeviceRGB",
+                    };
+                    return Some((base.to_vec(), comps, None));
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn string_or_stream_bytes(obj: &Object) -> Option<Vec<u8>> {
+    match obj {
+        Object::String(s, _) => Some(s.clone()),
+        Object::Stream(stream) => Some(stream.decompressed_content().unwrap_or_else(|_| stream.content.clone())),
+        _ => None,
+    }
+}
+
+/// Unpack `width * height` sub-byte-packed samples at `bpc` bits each into one byte per sample.
+/// `rescale` stretches each value to the full 0..=255 range, which is correct for continuous-tone
+/// components but wrong for `Indexed` palette indices — those must pass through unchanged since
+/// they're a lookup key, not a sample intensity.
+fn unpack_samples(raw: &[u8], width: u32, height: u32, components: usize, bpc: u8, rescale: bool) -> Vec<u8> {
+    if bpc == 8 {
+        return raw.to_vec();
+    }
+    let row_samples = width as usize * components;
+    let stride = (row_samples * bpc as usize).div_ceil(8);
+    let mut out = Vec::with_capacity(row_samples * height as usize);
+    let max = ((1u32 << bpc) - 1) as f32;
+    for row in 0..height as usize {
+        let row_bytes = &raw[(row * stride).min(raw.len())..((row + 1) * stride).min(raw.len())];
+        let mut bit_pos = 0usize;
+        for _ in 0..row_samples {
+            let mut value = 0u32;
+            for _ in 0..bpc {
+                let byte = row_bytes.get(bit_pos / 8).copied().unwrap_or_default();
+                let bit = (byte >> (7 - (bit_pos % 8))) & 1;
+                value = (value << 1) | bit as u32;
+                bit_pos += 1;
+            }
+            out.push(if rescale { ((value as f32 / max) * 255.0) as u8 } else { value as u8 });
+        }
+    }
+    out
+}
+
+/// Decode a PDF image XObject's pixel data into a `DynamicImage`, given its declared
+/// `/Width`, `/Height`, `/BitsPerComponent` and `/ColorSpace`, dispatching on `/Filter`.
+fn decode_image(doc: &Document, dict: &lopdf::Dictionary, content: &[u8]) -> Option<image::DynamicImage> {
+    let filter = image_filter_name(dict);
+
+    if filter == b"DCTDecode" {
+        return image::load_from_memory(content).ok();
+    }
+    if filter == b"JPXDecode" || filter == b"CCITTFaxDecode" || filter == b"JBIG2Decode" {
+        // Not supported yet: leave the stream untouched rather than corrupt it.
+        return None;
+    }
+
+    let width = match dict.get(b"Width") { Ok(Object::Integer(n)) => *n as u32, _ => return None };
+    let height = match dict.get(b"Height") { Ok(Object::Integer(n)) => *n as u32, _ => return None };
+    let bpc = match dict.get(b"BitsPerComponent") { Ok(Object::Integer(n)) => *n as u8, _ => 8 };
+
+    // FlateDecode / LZWDecode: rebuild a Stream so lopdf can inflate/un-LZW the raw samples.
+    let raw = lopdf::Stream::new(dict.clone(), content.to_vec()).decompressed_content().ok()?;
+
+    let color_space = dict.get(b"ColorSpace").ok()?;
+    let (base, comps, palette) = resolve_color_space(doc, color_space)?;
+
+    if let Some(lookup) = palette {
+        let indices = unpack_samples(&raw, width, height, 1, bpc, false);
+        let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+        for &idx in &indices {
+            let off = idx as usize * comps;
+            match comps {
+                4 => rgb.extend_from_slice(&cmyk_to_rgb(
+                    *lookup.get(off).unwrap_or(&0),
+                    *lookup.get(off + 1).unwrap_or(&0),
+                    *lookup.get(off + 2).unwrap_or(&0),
+                    *lookup.get(off + 3).unwrap_or(&0),
+                )),
+                3 => rgb.extend_from_slice(&[*lookup.get(off).unwrap_or(&0), *lookup.get(off + 1).unwrap_or(&0), *lookup.get(off + 2).unwrap_or(&0)]),
+                _ => { let g = *lookup.get(off).unwrap_or(&0); rgb.extend_from_slice(&[g, g, g]); }
+            }
+        }
+        return image::RgbImage::from_raw(width, height, rgb).map(image::DynamicImage::ImageRgb8);
+    }
+
+    let samples = unpack_samples(&raw, width, height, comps, bpc, true);
+    match base.as_slice() {
+        b"DeviceGray" | b"CalGray" => image::GrayImage::from_raw(width, height, samples).map(image::DynamicImage::ImageLuma8),
+        b"DeviceCMYK" => {
+            let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+            for px in samples.chunks_exact(4) {
+                rgb.extend_from_slice(&cmyk_to_rgb(px[0], px[1], px[2], px[3]));
+            }
+            image::RgbImage::from_raw(width, height, rgb).map(image::DynamicImage::ImageRgb8)
+        }
+        _ => image::RgbImage::from_raw(width, height, samples).map(image::DynamicImage::ImageRgb8),
+    }
+}
+
+fn cmyk_to_rgb(c: u8, m: u8, y: u8, k: u8) -> [u8; 3] {
+    let (c, m, y, k) = (c as f32 / 255.0, m as f32 / 255.0, y as f32 / 255.0, k as f32 / 255.0);
+    [
+        (255.0 * (1.0 - c) * (1.0 - k)) as u8,
+        (255.0 * (1.0 - m) * (1.0 - k)) as u8,
+        (255.0 * (1.0 - y) * (1.0 - k)) as u8,
+    ]
+}
+
+fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data).expect("writing to an in-memory buffer cannot fail");
+    encoder.finish().expect("finishing an in-memory buffer cannot fail")
+}
+
+/// Recompress image XObjects: downscale by a factor derived from `scale` (1..10) and re-embed
+/// either as a smaller JPEG, or — for images with a soft mask — as lossless Flate so the alpha
+/// channel stays meaningful.
+fn compress_impl(input: &str, output: &str, scale: i32, password: Option<&str>) -> Result<(), RustyPdfError> {
     if !(1..=10).contains(&scale) { return Err(RustyPdfError::BadScale(scale)); }
 
     let mut doc = Document::load(input)?;
+    decrypt_document(&mut doc, password.unwrap_or(""))?;
 
     // scale_factor 1.0 (scale=1) -> 0.25 (scale=10) (linear mapping)
     let scale_factor = 1.0 - ((scale - 1) as f32 / 9.0) * 0.75; // 1.0 .. 0.25
+    // JPEG quality: 100 (scale=1) to 30 (scale=10)
+    let jpeg_quality = (100 - (scale - 1) * 70 / 9) as u8; // 100..30
 
     let mut to_update: Vec<ObjectId> = Vec::new();
     for (id, obj) in doc.objects.iter() {
@@ -132,42 +1182,71 @@ fn compress_impl(input: &str, output: &str, scale: i32) -> Result<(), RustyPdfEr
     }
 
     for id in to_update {
-        if let Some(obj) = doc.objects.get_mut(&id) {
-            if let Ok(stream) = obj.as_stream_mut() {
-                let data = stream.content.clone();
-                // JPEG: starts with 0xFFD8
-                if data.starts_with(&[0xFF, 0xD8]) {
-                    if let Ok(img) = image::load_from_memory(&data) {
-                        let (w, h) = img.dimensions();
-                        let new_w = ((w as f32) * scale_factor).max(1.0) as u32;
-                        let new_h = ((h as f32) * scale_factor).max(1.0) as u32;
-                        let resized = img.resize(new_w, new_h, image::imageops::FilterType::Lanczos3);
-                        let mut buf: Vec<u8> = Vec::new();
-                        // JPEG quality: 100 (scale=1) to 30 (scale=10)
-                        let jpeg_quality = 100 - ((scale - 1) as u8 * 70 / 9); // 100..30
-                        let mut encoder = JpegEncoder::new_with_quality(&mut buf, jpeg_quality);
-                        if encoder.encode_image(&resized).is_ok() {
-                            stream.set_plain_content(buf);
+        // Snapshot the stream's dict/content (immutable borrow) before resolving referenced
+        // color spaces, which also need to read from `doc`.
+        let (dict, content, smask_id) = match doc.objects.get(&id).and_then(|o| o.as_stream().ok()) {
+            Some(stream) => {
+                let smask_id = match stream.dict.get(b"SMask") { Ok(&Object::Reference(r)) => Some(r), _ => None };
+                (stream.dict.clone(), stream.content.clone(), smask_id)
+            }
+            None => continue,
+        };
+
+        let Some(img) = decode_image(&doc, &dict, &content) else { continue };
+        let (w, h) = img.dimensions();
+        let new_w = ((w as f32) * scale_factor).max(1.0) as u32;
+        let new_h = ((h as f32) * scale_factor).max(1.0) as u32;
+        let resized = img.resize(new_w, new_h, image::imageops::FilterType::Lanczos3);
+
+        if let Some(smask_id) = smask_id {
+            // Images with a soft mask keep their alpha meaningful only losslessly.
+            let is_gray = matches!(resized, image::DynamicImage::ImageLuma8(_));
+            let samples = if is_gray { resized.to_luma8().into_raw() } else { resized.to_rgb8().into_raw() };
+            if let Some(obj) = doc.objects.get_mut(&id) {
+                if let Ok(stream) = obj.as_stream_mut() {
+                    stream.set_plain_content(zlib_compress(&samples));
+                    stream.dict.set("Filter", "FlateDecode");
+                    stream.dict.set("ColorSpace", if is_gray { "DeviceGray" } else { "DeviceRGB" });
+                    stream.dict.set("BitsPerComponent", 8i64);
+                    stream.dict.set("Width", new_w as i64);
+                    stream.dict.set("Height", new_h as i64);
+                    stream.dict.remove(b"DecodeParms");
+                }
+            }
+
+            // Downscale the soft mask itself to the same dimensions so it keeps lining up.
+            if let Some((mask_dict, mask_content)) = doc.objects.get(&smask_id).and_then(|o| o.as_stream().ok()).map(|s| (s.dict.clone(), s.content.clone())) {
+                if let Some(mask_img) = decode_image(&doc, &mask_dict, &mask_content) {
+                    let resized_mask = mask_img.resize_exact(new_w, new_h, image::imageops::FilterType::Lanczos3);
+                    let mask_samples = resized_mask.to_luma8().into_raw();
+                    if let Some(obj) = doc.objects.get_mut(&smask_id) {
+                        if let Ok(stream) = obj.as_stream_mut() {
+                            stream.set_plain_content(zlib_compress(&mask_samples));
+                            stream.dict.set("Filter", "FlateDecode");
+                            stream.dict.set("ColorSpace", "DeviceGray");
+                            stream.dict.set("BitsPerComponent", 8i64);
                             stream.dict.set("Width", new_w as i64);
                             stream.dict.set("Height", new_h as i64);
+                            stream.dict.remove(b"DecodeParms");
                         }
                     }
-                } else if data.starts_with(&[0x89, b'P', b'N', b'G']) {
-                    if let Ok(img) = image::load_from_memory(&data) {
-                        let (w, h) = img.dimensions();
-                        let new_w = ((w as f32) * scale_factor).max(1.0) as u32;
-                        let new_h = ((h as f32) * scale_factor).max(1.0) as u32;
-                        let resized = img.resize(new_w, new_h, image::imageops::FilterType::Lanczos3);
-                        let mut buf: Vec<u8> = Vec::new();
-                        if resized.write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png).is_ok() {
-                            stream.set_plain_content(buf);
-                            stream.dict.set("Width", new_w as i64);
-                            stream.dict.set("Height", new_h as i64);
-                        }
+                }
+            }
+        } else {
+            let is_gray = matches!(resized, image::DynamicImage::ImageLuma8(_) | image::DynamicImage::ImageLuma16(_));
+            let mut buf: Vec<u8> = Vec::new();
+            let mut encoder = JpegEncoder::new_with_quality(&mut buf, jpeg_quality);
+            if encoder.encode_image(&resized).is_ok() {
+                if let Some(obj) = doc.objects.get_mut(&id) {
+                    if let Ok(stream) = obj.as_stream_mut() {
+                        stream.set_plain_content(buf);
+                        stream.dict.set("Filter", "DCTDecode");
+                        stream.dict.set("ColorSpace", if is_gray { "DeviceGray" } else { "DeviceRGB" });
+                        stream.dict.set("BitsPerComponent", 8i64);
+                        stream.dict.set("Width", new_w as i64);
+                        stream.dict.set("Height", new_h as i64);
+                        stream.dict.remove(b"DecodeParms");
                     }
-                } else {
-                    // Unsupported image type -> skip
-                    continue;
                 }
             }
         }
@@ -178,16 +1257,182 @@ fn compress_impl(input: &str, output: &str, scale: i32) -> Result<(), RustyPdfEr
     Ok(())
 }
 
+/// Find a resource-dictionary key starting with `base` that doesn't already exist in `dict`,
+/// by appending an incrementing suffix (`base0`, `base1`, ...) until one is free.
+fn unique_resource_name(dict: &lopdf::Dictionary, base: &str) -> Vec<u8> {
+    let mut n = 0u32;
+    loop {
+        let candidate = format!("{base}{n}");
+        if !dict.has(candidate.as_bytes()) {
+            return candidate.into_bytes();
+        }
+        n += 1;
+    }
+}
+
+/// Overlay `text` as a rotated, translucent watermark on `pages` (1-based; `None` means every
+/// page), built with lopdf's content-stream API and appended to each page's existing content.
+fn stamp_impl(input: &str, output: &str, text: &str, opacity: f32, rotation: f32, pages: Option<&[u32]>) -> Result<(), RustyPdfError> {
+    use lopdf::content::{Content, Operation};
+
+    let mut doc = Document::load(input)?;
+
+    let page_map = doc.get_pages(); // page_num -> ObjectId
+    let target_pages: Vec<ObjectId> = match pages {
+        Some(nums) => nums.iter().filter_map(|n| page_map.get(n).copied()).collect(),
+        None => page_map.values().copied().collect(),
+    };
+
+    // A single Helvetica font and translucency ExtGState are shared by every stamped page.
+    let font_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica"
+    });
+    let gs_id = doc.add_object(lopdf::dictionary! {
+        "Type" => "ExtGState",
+        "ca" => opacity as f64
+    });
+
+    let font_size = 48.0f32;
+    let radians = rotation.to_radians();
+    let (cos, sin) = (radians.cos(), radians.sin());
+
+    for page_id in target_pages {
+        let (width, height) = match resolve_inherited(&doc, page_id, b"MediaBox") {
+            Some(Object::Array(arr)) if arr.len() == 4 => {
+                let as_f32 = |o: &Object| match o { Object::Integer(n) => *n as f32, Object::Real(n) => *n, _ => 0.0 };
+                (as_f32(&arr[2]) - as_f32(&arr[0]), as_f32(&arr[3]) - as_f32(&arr[1]))
+            }
+            _ => (612.0, 792.0), // US Letter fallback
+        };
+        let (cx, cy) = (width / 2.0, height / 2.0);
+
+        // Merge the font/ExtGState into this page's own Resources (inline, whether or not the
+        // original Resources entry was inline or an indirect reference). Names are picked to
+        // avoid colliding with whatever the page's own content stream already uses: "F0"/"GS0"
+        // are extremely common auto-generated names, and overwriting one would silently repoint
+        // the original content at our watermark font/graphics state.
+        let mut resources = match resolve_inherited(&doc, page_id, b"Resources") {
+            Some(Object::Dictionary(d)) => d,
+            Some(Object::Reference(r)) => match doc.get_object(r) {
+                Ok(Object::Dictionary(d)) => d.clone(),
+                _ => lopdf::Dictionary::new(),
+            },
+            _ => lopdf::Dictionary::new(),
+        };
+        // The /Font and /ExtGState subdictionaries may themselves be indirect references.
+        let subdict = |entry: Option<&Object>| match entry {
+            Some(Object::Dictionary(d)) => d.clone(),
+            Some(&Object::Reference(r)) => match doc.get_object(r) {
+                Ok(Object::Dictionary(d)) => d.clone(),
+                _ => lopdf::Dictionary::new(),
+            },
+            _ => lopdf::Dictionary::new(),
+        };
+        let mut fonts = subdict(resources.get(b"Font").ok());
+        let font_name = unique_resource_name(&fonts, "WMFont");
+        fonts.set(font_name.clone(), font_id);
+        resources.set("Font", Object::Dictionary(fonts));
+        let mut ext_gstates = subdict(resources.get(b"ExtGState").ok());
+        let gs_name = unique_resource_name(&ext_gstates, "WMGS");
+        ext_gstates.set(gs_name.clone(), gs_id);
+        resources.set("ExtGState", Object::Dictionary(ext_gstates));
+
+        // q/Q around the overlay so its ExtGState and text matrix don't leak into anything else.
+        let content = Content {
+            operations: vec![
+                Operation::new("q", vec![]),
+                Operation::new("gs", vec![Object::Name(gs_name)]),
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec![Object::Name(font_name), Object::Real(font_size)]),
+                Operation::new(
+                    "Tm",
+                    vec![
+                        Object::Real(cos),
+                        Object::Real(sin),
+                        Object::Real(-sin),
+                        Object::Real(cos),
+                        Object::Real(cx),
+                        Object::Real(cy),
+                    ],
+                ),
+                Operation::new("Tj", vec![Object::String(text.as_bytes().to_vec(), lopdf::StringFormat::Literal)]),
+                Operation::new("ET", vec![]),
+                Operation::new("Q", vec![]),
+            ],
+        };
+        let encoded = content.encode()?;
+        let stream_id = doc.add_object(lopdf::Stream::new(lopdf::dictionary! {}, encoded));
+
+        if let Ok(page_obj) = doc.get_object_mut(page_id) {
+            if let Ok(page_dict) = page_obj.as_dict_mut() {
+                page_dict.set("Resources", Object::Dictionary(resources));
+
+                // Promote a single /Contents stream to an array before appending the overlay.
+                let mut contents: Vec<Object> = match page_dict.get(b"Contents") {
+                    Ok(Object::Array(arr)) => arr.clone(),
+                    Ok(&Object::Reference(r)) => vec![Object::Reference(r)],
+                    _ => Vec::new(),
+                };
+                contents.push(Object::Reference(stream_id));
+                page_dict.set("Contents", Object::Array(contents));
+            }
+        }
+    }
+
+    doc.compress();
+    doc.save(output)?;
+    Ok(())
+}
+
 #[pyfunction]
-fn merge_pdfs(py: Python<'_>, inputs: Vec<String>, output: String) -> PyResult<()> {
+#[pyo3(signature = (inputs, output, password=None))]
+fn merge_pdfs(py: Python<'_>, inputs: Vec<String>, output: String, password: Option<String>) -> PyResult<()> {
     // release GIL while heavy work
-    py.allow_threads(|| merge_impl(&inputs.iter().map(|s| s.as_str()).collect::<Vec<_>>() , &output))?;
+    py.allow_threads(|| merge_impl(&inputs.iter().map(|s| s.as_str()).collect::<Vec<_>>(), &output, password.as_deref()))?;
     Ok(())
 }
 
 #[pyfunction]
-fn compress_pdf(py: Python<'_>, input: String, output: String, scale: i32) -> PyResult<()> {
-    py.allow_threads(|| compress_impl(&input, &output, scale))?;
+#[pyo3(signature = (input, output, scale, password=None))]
+fn compress_pdf(py: Python<'_>, input: String, output: String, scale: i32, password: Option<String>) -> PyResult<()> {
+    py.allow_threads(|| compress_impl(&input, &output, scale, password.as_deref()))?;
+    Ok(())
+}
+
+/// Protect `input` with the standard security handler (user/owner passwords plus a permission
+/// bitmask per PDF 32000-1:2008 Table 22) and write the encrypted file to `output`.
+fn encrypt_pdf_impl(input: &str, output: &str, user_password: &str, owner_password: &str, permissions: i32) -> Result<(), RustyPdfError> {
+    let mut doc = Document::load(input)?;
+    encrypt_document(&mut doc, user_password, owner_password, permissions)?;
+    doc.save(output)?;
+    Ok(())
+}
+
+#[pyfunction]
+fn encrypt_pdf(py: Python<'_>, input: String, output: String, user_password: String, owner_password: String, permissions: i32) -> PyResult<()> {
+    py.allow_threads(|| encrypt_pdf_impl(&input, &output, &user_password, &owner_password, permissions))?;
+    Ok(())
+}
+
+#[pyfunction]
+fn split_pdf(py: Python<'_>, input: String, output: String, pages: Vec<u32>) -> PyResult<()> {
+    py.allow_threads(|| split_impl(&input, &output, &pages))?;
+    Ok(())
+}
+
+/// Alias of `split_pdf` for callers extracting a handful of pages rather than "splitting" a document.
+#[pyfunction]
+fn extract_pages(py: Python<'_>, input: String, output: String, pages: Vec<u32>) -> PyResult<()> {
+    py.allow_threads(|| split_impl(&input, &output, &pages))?;
+    Ok(())
+}
+
+#[pyfunction]
+#[pyo3(signature = (input, output, text, opacity, rotation, pages=None))]
+fn stamp_pdf(py: Python<'_>, input: String, output: String, text: String, opacity: f32, rotation: f32, pages: Option<Vec<u32>>) -> PyResult<()> {
+    py.allow_threads(|| stamp_impl(&input, &output, &text, opacity, rotation, pages.as_deref()))?;
     Ok(())
 }
 
@@ -195,5 +1440,112 @@ fn compress_pdf(py: Python<'_>, input: String, output: String, scale: i32) -> Py
 fn _rustypdf(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(merge_pdfs, m)?)?;
     m.add_function(wrap_pyfunction!(compress_pdf, m)?)?;
+    m.add_function(wrap_pyfunction!(encrypt_pdf, m)?)?;
+    m.add_function(wrap_pyfunction!(split_pdf, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_pages, m)?)?;
+    m.add_function(wrap_pyfunction!(stamp_pdf, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a minimal but valid single-tree PDF with `page_count` blank pages, suitable for
+    /// exercising document-level logic without going through a file on disk.
+    fn build_test_document(page_count: u32) -> Document {
+        let mut doc = Document::new();
+        let pages_id = doc.new_object_id();
+
+        let mut kids = Vec::new();
+        for _ in 0..page_count {
+            let page_id = doc.add_object(lopdf::dictionary! {
+                "Type" => "Page",
+                "Parent" => Object::Reference(pages_id)
+            });
+            kids.push(Object::Reference(page_id));
+        }
+        doc.set_object(pages_id, lopdf::dictionary! {
+            "Type" => "Pages",
+            "Kids" => Object::Array(kids),
+            "Count" => page_count as i64
+        });
+
+        let catalog_id = doc.add_object(lopdf::dictionary! {
+            "Type" => "Catalog",
+            "Pages" => Object::Reference(pages_id)
+        });
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+        doc
+    }
+
+    #[test]
+    fn rc4_round_trips() {
+        let key = b"a reasonably long test key";
+        let data = b"the quick brown fox jumps over the lazy dog";
+        let ciphertext = rc4_crypt(key, data);
+        assert_ne!(ciphertext, data);
+        assert_eq!(rc4_crypt(key, &ciphertext), data);
+    }
+
+    #[test]
+    fn encrypt_then_decrypt_document_round_trips() {
+        let mut doc = build_test_document(1);
+        let title_id = doc.add_object(Object::String(b"secret title".to_vec(), lopdf::StringFormat::Literal));
+
+        encrypt_document(&mut doc, "user-pw", "owner-pw", -4).expect("encrypt");
+        assert!(doc.trailer.has(b"Encrypt"));
+        match doc.get_object(title_id).expect("object survives encryption") {
+            Object::String(s, _) => assert_ne!(s.as_slice(), b"secret title"),
+            other => panic!("expected an encrypted string, got {other:?}"),
+        }
+
+        decrypt_document(&mut doc, "user-pw").expect("decrypt with the right password");
+        assert!(!doc.trailer.has(b"Encrypt"));
+        match doc.get_object(title_id).expect("object survives decryption") {
+            Object::String(s, _) => assert_eq!(s.as_slice(), b"secret title"),
+            other => panic!("expected a plaintext string, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decrypt_document_rejects_wrong_password() {
+        let mut doc = build_test_document(1);
+        encrypt_document(&mut doc, "user-pw", "owner-pw", -4).expect("encrypt");
+        assert!(matches!(decrypt_document(&mut doc, "not-the-password"), Err(RustyPdfError::InvalidPassword)));
+    }
+
+    #[test]
+    fn merge_pdfs_preserves_page_count_and_outlines() {
+        let dir = std::env::temp_dir();
+        let input_a = dir.join("rustypdf_test_merge_a.pdf");
+        let input_b = dir.join("rustypdf_test_merge_b.pdf");
+        let output = dir.join("rustypdf_test_merge_out.pdf");
+
+        let mut doc_a = build_test_document(1);
+        let outline_item = doc_a.add_object(lopdf::dictionary! {
+            "Title" => Object::String(b"Chapter 1".to_vec(), lopdf::StringFormat::Literal)
+        });
+        let outlines_id = doc_a.add_object(lopdf::dictionary! {
+            "Type" => "Outlines",
+            "First" => Object::Reference(outline_item),
+            "Last" => Object::Reference(outline_item),
+            "Count" => 1i64
+        });
+        doc_a.catalog_mut().expect("catalog").set("Outlines", Object::Reference(outlines_id));
+        doc_a.save(&input_a).expect("save input a");
+
+        let doc_b = build_test_document(1);
+        doc_b.save(&input_b).expect("save input b");
+
+        merge_impl(&[input_a.to_str().unwrap(), input_b.to_str().unwrap()], output.to_str().unwrap(), None).expect("merge");
+
+        let merged = Document::load(&output).expect("load merged output");
+        assert_eq!(merged.get_pages().len(), 2);
+        assert!(merged.catalog().expect("merged catalog").has(b"Outlines"));
+
+        let _ = std::fs::remove_file(&input_a);
+        let _ = std::fs::remove_file(&input_b);
+        let _ = std::fs::remove_file(&output);
+    }
+}